@@ -1,5 +1,5 @@
 // DSP components for Beverley bitcrusher
-use std::f32::consts::E;
+use std::f32::consts::{E, PI};
 
 /// Simple bit crusher that quantizes input to a specified number of levels
 /// with optional smoothing between quantization levels
@@ -136,11 +136,16 @@ pub struct InterpolatedBitCrusher {
     auto_gain: bool,
     symmetric_crush: bool,
     peak: ExponentialPeak,
+    srr_ratio: f32,
+    srr_phase: f32,
+    srr_held: f32,
+    srr_prev_held: f32,
 }
 
 impl InterpolatedBitCrusher {
     const THRESHOLD: f32 = 0.05;
     const THRESHOLD_RECIPROCAL: f32 = 1.0 / Self::THRESHOLD;
+    const MIN_SRR_RATIO: f32 = 1.0 / 512.0;
 
     pub fn new(sample_rate: f32) -> Self {
         Self {
@@ -154,6 +159,10 @@ impl InterpolatedBitCrusher {
             auto_gain: false,
             symmetric_crush: false,
             peak: ExponentialPeak::new(sample_rate),
+            srr_ratio: 1.0,
+            srr_phase: 0.0,
+            srr_held: 0.0,
+            srr_prev_held: 0.0,
         }
     }
 
@@ -189,6 +198,13 @@ impl InterpolatedBitCrusher {
         self.symmetric_crush = symmetric;
     }
 
+    /// Sets the sample-and-hold ratio, i.e. the effective reduced sample
+    /// rate divided by the host sample rate, in (0, 1]. 1.0 disables
+    /// reduction (every sample is held).
+    pub fn set_sample_rate_reduction(&mut self, ratio: f32) {
+        self.srr_ratio = ratio.clamp(Self::MIN_SRR_RATIO, 1.0);
+    }
+
     pub fn apply(&mut self, input: f32) -> f32 {
         let abs_input = input.abs();
         let sign_input = input.signum();
@@ -213,14 +229,43 @@ impl InterpolatedBitCrusher {
             self.asymmetric_quantize(sign_input, gamma_transformed)
         };
 
+        // Sample-and-hold (sample rate reduction)
+        let held = self.sample_and_hold(quantized);
+
         // Inverse gamma transform and gain compensation
         if self.auto_gain {
-            sign_input * quantized.powf(self.gamma) / gain
+            sign_input * held.powf(self.gamma) / gain
         } else {
-            sign_input * self.gain_reciprocal * quantized.powf(self.gamma)
+            sign_input * self.gain_reciprocal * held.powf(self.gamma)
         }
     }
 
+    /// Latches a new held value each time the phase accumulator crosses
+    /// an integer boundary, otherwise crossfades between the previous and
+    /// current held values over the hold interval so that automating
+    /// `srr_ratio` doesn't zipper.
+    fn sample_and_hold(&mut self, quantized: f32) -> f32 {
+        // At ratio 1.0 every sample latches immediately, which would make
+        // the crossfade below always read back the *previous* held value
+        // (a one-sample delay rather than a bypass), so pass through directly.
+        if self.srr_ratio >= 1.0 {
+            self.srr_phase = 0.0;
+            self.srr_prev_held = quantized;
+            self.srr_held = quantized;
+            return quantized;
+        }
+
+        self.srr_phase += self.srr_ratio;
+
+        if self.srr_phase >= 1.0 {
+            self.srr_phase -= self.srr_phase.floor();
+            self.srr_prev_held = self.srr_held;
+            self.srr_held = quantized;
+        }
+
+        self.srr_prev_held + self.srr_phase * (self.srr_held - self.srr_prev_held)
+    }
+
     fn asymmetric_quantize(&self, sign_input: f32, gamma_transformed: f32) -> f32 {
         // Map signed input to 0-1 range
         let crush_input = ((gamma_transformed * sign_input) + 1.0) * 0.5;
@@ -246,6 +291,381 @@ impl InterpolatedBitCrusher {
 
     pub fn reset(&mut self) {
         self.peak.reset();
+        self.srr_phase = 0.0;
+        self.srr_held = 0.0;
+        self.srr_prev_held = 0.0;
+    }
+}
+
+/// Nonzero odd-indexed taps of the half-band lowpass prototype shared by
+/// every oversampling stage (one side of the symmetric impulse response,
+/// at offsets 1, 3, 5, 7 samples from the center; the center tap itself
+/// is always exactly 0.5 and every other even-offset tap is exactly
+/// zero). Normalized so the full kernel (center plus both sides) sums to
+/// unity, giving the filter unity gain at DC.
+const HALF_BAND_TAPS: [f32; 4] = [0.3284, -0.1073, 0.0458, -0.0169];
+const HALF_BAND_TAP_COUNT: usize = HALF_BAND_TAPS.len();
+
+/// Length of a [`HalfBandStage`]'s history buffer: the center tap plus
+/// [`HALF_BAND_TAP_COUNT`] nonzero taps on each side, spaced two samples
+/// apart.
+const HALF_BAND_HISTORY_LEN: usize = 4 * HALF_BAND_TAP_COUNT - 1;
+
+/// Index of the center tap within the history buffer.
+const HALF_BAND_CENTER: usize = 2 * HALF_BAND_TAP_COUNT - 1;
+
+/// Maximum supported oversampling factor (2^[`MAX_OVERSAMPLING_STAGES`]).
+pub const MAX_OVERSAMPLING_FACTOR: usize = 8;
+
+/// Number of cascaded half-band stages needed to reach
+/// [`MAX_OVERSAMPLING_FACTOR`].
+const MAX_OVERSAMPLING_STAGES: usize = 3;
+
+/// A single 2x half-band FIR stage, implemented as a direct-form
+/// convolution against [`HALF_BAND_TAPS`] (plus the fixed 0.5 center
+/// tap). The same stage is reused for both interpolation (upsampling)
+/// and decimation (downsampling).
+struct HalfBandStage {
+    history: [f32; HALF_BAND_HISTORY_LEN],
+}
+
+impl HalfBandStage {
+    fn new() -> Self {
+        Self {
+            history: [0.0; HALF_BAND_HISTORY_LEN],
+        }
+    }
+
+    fn reset(&mut self) {
+        self.history = [0.0; HALF_BAND_HISTORY_LEN];
+    }
+
+    /// Pushes one sample through the half-band filter and returns the
+    /// filtered output.
+    fn push(&mut self, input: f32) -> f32 {
+        let len = self.history.len();
+        self.history.copy_within(0..len - 1, 1);
+        self.history[0] = input;
+
+        let mut filtered = 0.5 * self.history[HALF_BAND_CENTER];
+        for (i, &tap) in HALF_BAND_TAPS.iter().enumerate() {
+            let offset = 2 * i + 1;
+            filtered += tap * (self.history[HALF_BAND_CENTER - offset] + self.history[HALF_BAND_CENTER + offset]);
+        }
+
+        filtered
+    }
+
+    /// Interpolates one input sample into two output samples by inserting
+    /// a zero between samples and running the filter. The output is
+    /// scaled by 2x to restore the amplitude lost to zero insertion.
+    fn interpolate(&mut self, input: f32) -> (f32, f32) {
+        let even = 2.0 * self.push(input);
+        let odd = 2.0 * self.push(0.0);
+        (even, odd)
+    }
+
+    /// Decimates two input samples into one output sample by filtering
+    /// and keeping every other sample.
+    fn decimate(&mut self, first: f32, second: f32) -> f32 {
+        self.push(first);
+        self.push(second)
+    }
+}
+
+/// Anti-aliased oversampler built from cascaded [`HalfBandStage`]s,
+/// supporting 1x (bypass), 2x, 4x, or 8x oversampling. Wrap a nonlinear
+/// process (e.g. bit crushing) between [`upsample`](Self::upsample) and
+/// [`downsample`](Self::downsample) to keep the aliasing it generates out
+/// of the audible band.
+pub struct HalfBandOversampler {
+    up_stages: [HalfBandStage; MAX_OVERSAMPLING_STAGES],
+    down_stages: [HalfBandStage; MAX_OVERSAMPLING_STAGES],
+    num_stages: usize,
+}
+
+impl HalfBandOversampler {
+    pub fn new() -> Self {
+        Self {
+            up_stages: [HalfBandStage::new(), HalfBandStage::new(), HalfBandStage::new()],
+            down_stages: [HalfBandStage::new(), HalfBandStage::new(), HalfBandStage::new()],
+            num_stages: 0,
+        }
+    }
+
+    /// Sets the oversampling factor, rounded down to the nearest
+    /// supported power of two (1x, 2x, 4x, or 8x).
+    pub fn set_factor(&mut self, factor: f32) {
+        let factor = factor.max(1.0) as usize;
+        self.num_stages = match factor {
+            f if f >= 8 => 3,
+            f if f >= 4 => 2,
+            f if f >= 2 => 1,
+            _ => 0,
+        };
+    }
+
+    /// The active oversampling factor (1, 2, 4, or 8).
+    pub fn factor(&self) -> usize {
+        1 << self.num_stages
+    }
+
+    /// Total filter latency introduced by the active stages, in samples
+    /// at the host (non-oversampled) rate. Each stage's signal passes
+    /// through both an interpolation and a decimation filter, so its
+    /// group delay is counted twice.
+    pub fn latency_samples(&self) -> f32 {
+        let mut latency = 0.0;
+        let mut rate_factor = 1.0;
+        for _ in 0..self.num_stages {
+            rate_factor *= 2.0;
+            latency += 2.0 * HALF_BAND_CENTER as f32 / rate_factor;
+        }
+        latency
+    }
+
+    /// Upsamples a single input sample to [`factor`](Self::factor) output
+    /// samples, written into the front of the returned buffer.
+    pub fn upsample(&mut self, input: f32) -> [f32; MAX_OVERSAMPLING_FACTOR] {
+        let mut buf = [0.0; MAX_OVERSAMPLING_FACTOR];
+        let mut tmp = [0.0; MAX_OVERSAMPLING_FACTOR];
+        buf[0] = input;
+
+        let mut count = 1;
+        for stage in self.up_stages[..self.num_stages].iter_mut() {
+            for i in 0..count {
+                let (even, odd) = stage.interpolate(buf[i]);
+                tmp[2 * i] = even;
+                tmp[2 * i + 1] = odd;
+            }
+            count *= 2;
+            buf[..count].copy_from_slice(&tmp[..count]);
+        }
+
+        buf
+    }
+
+    /// Decimates [`factor`](Self::factor) oversampled input samples back
+    /// down to a single output sample.
+    pub fn downsample(&mut self, samples: &[f32; MAX_OVERSAMPLING_FACTOR]) -> f32 {
+        let mut buf = *samples;
+        let mut count = self.factor();
+
+        for stage in self.down_stages[..self.num_stages].iter_mut().rev() {
+            let half = count / 2;
+            for i in 0..half {
+                buf[i] = stage.decimate(buf[2 * i], buf[2 * i + 1]);
+            }
+            count = half;
+        }
+
+        buf[0]
+    }
+
+    pub fn reset(&mut self) {
+        for stage in self.up_stages.iter_mut() {
+            stage.reset();
+        }
+        for stage in self.down_stages.iter_mut() {
+            stage.reset();
+        }
+    }
+}
+
+/// Output tap selected from a [`StateVariableFilter`]'s shared integrator
+/// state.
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    LowPass,
+    BandPass,
+    HighPass,
+    Notch,
+}
+
+/// Zero-delay-feedback (TPT) state-variable filter, after Vadim
+/// Zavalishin's "The Art of VA Filter Design". A single pair of
+/// integrator states yields simultaneous lowpass, bandpass, highpass, and
+/// notch taps, so switching `FilterMode` doesn't disturb the filter's
+/// state.
+pub struct StateVariableFilter {
+    k: f32,
+    a1: f32,
+    a2: f32,
+    a3: f32,
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl StateVariableFilter {
+    pub fn new() -> Self {
+        let mut filter = Self {
+            k: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+            a3: 0.0,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+        };
+        filter.set_coefficients(1000.0, 0.707, 48000.0);
+        filter
+    }
+
+    /// Recomputes the filter coefficients for a new cutoff (Hz) and
+    /// resonance (Q). Callers are expected to gate this behind a
+    /// cutoff/resonance-changed check, mirroring the cached-parameter
+    /// pattern in `Beverley::update_crushers_if_changed`.
+    pub fn set_coefficients(&mut self, cutoff: f32, resonance: f32, sample_rate: f32) {
+        let g = (PI * cutoff / sample_rate).tan();
+        self.k = 1.0 / resonance;
+        self.a1 = 1.0 / (1.0 + g * (g + self.k));
+        self.a2 = g * self.a1;
+        self.a3 = g * self.a2;
+    }
+
+    pub fn process(&mut self, input: f32, mode: FilterMode) -> f32 {
+        let v3 = input - self.ic2eq;
+        let v1 = self.a1 * self.ic1eq + self.a2 * v3;
+        let v2 = self.ic2eq + self.a2 * self.ic1eq + self.a3 * v3;
+
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        match mode {
+            FilterMode::LowPass => v2,
+            FilterMode::BandPass => v1,
+            FilterMode::HighPass => input - self.k * v1 - v2,
+            FilterMode::Notch => input - self.k * v1,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+}
+
+/// One-pole DC blocker removing the steady offset that asymmetric
+/// crushing and the gamma transform can introduce, via
+/// `y[n] = x[n] - x[n-1] + R * y[n-1]`.
+pub struct DcBlocker {
+    r: f32,
+    prev_input: f32,
+    prev_output: f32,
+}
+
+impl DcBlocker {
+    /// Corner frequency, in Hz, below which the blocker removes offset.
+    const CORNER_HZ: f32 = 10.0;
+
+    pub fn new(sample_rate: f32) -> Self {
+        let mut blocker = Self {
+            r: 0.0,
+            prev_input: 0.0,
+            prev_output: 0.0,
+        };
+        blocker.set_sample_rate(sample_rate);
+        blocker
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.r = 1.0 - (2.0 * PI * Self::CORNER_HZ / sample_rate);
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let output = input - self.prev_input + self.r * self.prev_output;
+        self.prev_input = input;
+        self.prev_output = output;
+        output
+    }
+
+    pub fn reset(&mut self) {
+        self.prev_input = 0.0;
+        self.prev_output = 0.0;
+    }
+}
+
+/// Waveform generated by an [`Lfo`].
+#[derive(Clone, Copy, PartialEq)]
+pub enum LfoWaveform {
+    Sine,
+    Triangle,
+    SampleAndHold,
+}
+
+/// Number of entries in the cosine wavetable shared by every `Lfo`
+/// (excluding the trailing guard sample).
+const LFO_TABLE_SIZE: usize = 512;
+
+/// Low-frequency oscillator for modulating crush parameters. Backed by a
+/// precomputed cosine wavetable read with linear interpolation, so
+/// per-sample evaluation avoids calling `f32::sin`/`cos` in the audio
+/// thread.
+pub struct Lfo {
+    // One extra guard sample, equal to the first, so interpolation never
+    // needs to wrap the index.
+    cosine_table: [f32; LFO_TABLE_SIZE + 1],
+    phase: f32,
+    held_value: f32,
+    rng_state: u32,
+}
+
+impl Lfo {
+    pub fn new() -> Self {
+        let mut cosine_table = [0.0; LFO_TABLE_SIZE + 1];
+        for (i, entry) in cosine_table.iter_mut().enumerate() {
+            *entry = (i as f32 * std::f32::consts::TAU / LFO_TABLE_SIZE as f32).cos();
+        }
+
+        Self {
+            cosine_table,
+            phase: 0.0,
+            held_value: 0.0,
+            rng_state: 0x1234_5678,
+        }
+    }
+
+    /// Advances the phase accumulator by `rate / sample_rate` and returns
+    /// the next LFO value in [-1, 1] for the selected waveform.
+    pub fn advance(&mut self, rate: f32, sample_rate: f32, waveform: LfoWaveform) -> f32 {
+        let previous_phase = self.phase;
+        self.phase = (self.phase + rate / sample_rate).fract();
+
+        match waveform {
+            LfoWaveform::Sine => self.cosine_lookup(self.phase),
+            LfoWaveform::Triangle => {
+                if self.phase < 0.5 {
+                    1.0 - 4.0 * self.phase
+                } else {
+                    4.0 * self.phase - 3.0
+                }
+            }
+            LfoWaveform::SampleAndHold => {
+                if self.phase < previous_phase {
+                    self.held_value = self.next_random();
+                }
+                self.held_value
+            }
+        }
+    }
+
+    fn cosine_lookup(&self, phase: f32) -> f32 {
+        let position = phase * LFO_TABLE_SIZE as f32;
+        let index = position.floor() as usize;
+        let frac = position - index as f32;
+        self.cosine_table[index] + frac * (self.cosine_table[index + 1] - self.cosine_table[index])
+    }
+
+    /// xorshift32, enough randomness for a sample-and-hold LFO.
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        (self.rng_state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+        self.held_value = 0.0;
     }
 }
 
@@ -257,7 +677,7 @@ mod tests {
     fn test_bit_crusher_basic() {
         let mut crusher = BitCrusher::new();
         crusher.set_levels(3); // 2-bit (4 levels)
-        crusher.set_steepness(1.0); // Hard quantization
+        crusher.set_steepness(1.0, 1.0); // Hard quantization
 
         let output = crusher.crush(0.5);
         assert!((output - 0.333).abs() < 0.01 || (output - 0.666).abs() < 0.01);
@@ -267,10 +687,65 @@ mod tests {
     fn test_interpolated_crusher() {
         let mut crusher = InterpolatedBitCrusher::new(48000.0);
         crusher.set_depth(4.0); // 4-bit
-        crusher.set_gamma(1.0); // No gamma
-        crusher.set_smoothing(0.0); // Hard quantization
+        crusher.set_gamma(1.0, 1.0); // No gamma
+        crusher.set_steepness(1.0, 1.0); // Hard quantization
 
         let output = crusher.apply(0.5);
         assert!(output > 0.0 && output < 1.0);
     }
+
+    #[test]
+    fn test_half_band_oversampler_round_trip() {
+        let mut oversampler = HalfBandOversampler::new();
+        oversampler.set_factor(4.0);
+        assert_eq!(oversampler.factor(), 4);
+
+        let mut output = 0.0;
+        for _ in 0..64 {
+            let up = oversampler.upsample(1.0);
+            output = oversampler.downsample(&up);
+        }
+
+        // A constant input should settle to a constant output once the
+        // filters' transients have died out.
+        assert!((output - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_state_variable_filter_lowpass_passes_dc() {
+        let mut filter = StateVariableFilter::new();
+        filter.set_coefficients(1000.0, 0.707, 48000.0);
+
+        let mut output = 0.0;
+        for _ in 0..256 {
+            output = filter.process(1.0, FilterMode::LowPass);
+        }
+
+        // A DC input should pass through a lowpass filter unattenuated
+        // once its state has settled.
+        assert!((output - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_dc_blocker_removes_offset() {
+        let mut blocker = DcBlocker::new(48000.0);
+
+        let mut output = 0.0;
+        for _ in 0..48000 {
+            output = blocker.process(0.5);
+        }
+
+        // A constant (DC) input should be driven toward zero.
+        assert!(output.abs() < 0.01);
+    }
+
+    #[test]
+    fn test_lfo_sine_stays_in_range() {
+        let mut lfo = Lfo::new();
+
+        for _ in 0..1000 {
+            let value = lfo.advance(2.0, 48000.0, LfoWaveform::Sine);
+            assert!((-1.0..=1.0).contains(&value));
+        }
+    }
 }
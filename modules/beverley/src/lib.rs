@@ -5,12 +5,22 @@ use nih_plug::prelude::*;
 use std::sync::Arc;
 
 mod dsp;
-use dsp::InterpolatedBitCrusher;
+use dsp::{DcBlocker, FilterMode, HalfBandOversampler, InterpolatedBitCrusher, Lfo, LfoWaveform, StateVariableFilter};
 
 pub struct Beverley {
     params: Arc<BeverleyParams>,
     crusher_left: InterpolatedBitCrusher,
     crusher_right: InterpolatedBitCrusher,
+    oversampler_left: HalfBandOversampler,
+    oversampler_right: HalfBandOversampler,
+    filter_pre_left: StateVariableFilter,
+    filter_pre_right: StateVariableFilter,
+    filter_post_left: StateVariableFilter,
+    filter_post_right: StateVariableFilter,
+    dc_blocker_left: DcBlocker,
+    dc_blocker_right: DcBlocker,
+    lfo: Lfo,
+    sample_rate: f32,
 
     // Cached parameter values to avoid redundant updates
     cached_bit_depth: f32,
@@ -19,6 +29,10 @@ pub struct Beverley {
     cached_gain_db: f32,
     cached_auto_gain: bool,
     cached_crush_mode: bool,
+    cached_oversampling: i32,
+    cached_sample_rate_reduction: f32,
+    cached_filter_cutoff: f32,
+    cached_filter_resonance: f32,
 }
 
 #[derive(Params)]
@@ -47,6 +61,51 @@ pub struct BeverleyParams {
     /// Crush mode (false = asymmetric, true = symmetric)
     #[id = "crush_mode"]
     pub crush_mode: BoolParam,
+
+    /// Oversampling factor exponent: 0 = 1x (off), 1 = 2x, 2 = 4x, 3 = 8x
+    #[id = "oversampling"]
+    pub oversampling: IntParam,
+
+    /// Sample rate reduction target, in Hz. At the host sample rate, no
+    /// reduction is applied.
+    #[id = "sample_rate_reduction"]
+    pub sample_rate_reduction: FloatParam,
+
+    /// Filter cutoff frequency, in Hz
+    #[id = "filter_cutoff"]
+    pub filter_cutoff: FloatParam,
+
+    /// Filter resonance (Q)
+    #[id = "filter_resonance"]
+    pub filter_resonance: FloatParam,
+
+    /// Filter mode: 0 = lowpass, 1 = bandpass, 2 = highpass, 3 = notch
+    #[id = "filter_mode"]
+    pub filter_mode: IntParam,
+
+    /// Filter placement: 0 = off, 1 = pre-crush, 2 = post-crush, 3 = both
+    #[id = "filter_position"]
+    pub filter_position: IntParam,
+
+    /// DC blocker toggle (on by default)
+    #[id = "dc_block"]
+    pub dc_block: BoolParam,
+
+    /// LFO rate, in Hz
+    #[id = "lfo_rate"]
+    pub lfo_rate: FloatParam,
+
+    /// LFO modulation depth (0 = no modulation, 1 = full range)
+    #[id = "lfo_depth"]
+    pub lfo_depth: FloatParam,
+
+    /// LFO modulation target: 0 = bit depth, 1 = gamma, 2 = gain
+    #[id = "lfo_target"]
+    pub lfo_target: IntParam,
+
+    /// LFO waveform: 0 = sine, 1 = triangle, 2 = sample-and-hold
+    #[id = "lfo_waveform"]
+    pub lfo_waveform: IntParam,
 }
 
 impl Default for Beverley {
@@ -55,12 +114,26 @@ impl Default for Beverley {
             params: Arc::new(BeverleyParams::default()),
             crusher_left: InterpolatedBitCrusher::new(48000.0),
             crusher_right: InterpolatedBitCrusher::new(48000.0),
+            oversampler_left: HalfBandOversampler::new(),
+            oversampler_right: HalfBandOversampler::new(),
+            filter_pre_left: StateVariableFilter::new(),
+            filter_pre_right: StateVariableFilter::new(),
+            filter_post_left: StateVariableFilter::new(),
+            filter_post_right: StateVariableFilter::new(),
+            dc_blocker_left: DcBlocker::new(48000.0),
+            dc_blocker_right: DcBlocker::new(48000.0),
+            lfo: Lfo::new(),
+            sample_rate: 48000.0,
             cached_bit_depth: 4.0,
             cached_gamma: 1.0, // 10^0 = 1.0
             cached_smoothing: 0.0,
             cached_gain_db: 0.0,
             cached_auto_gain: false,
             cached_crush_mode: false,
+            cached_oversampling: 0,
+            cached_sample_rate_reduction: 48000.0,
+            cached_filter_cutoff: 1000.0,
+            cached_filter_resonance: 0.707,
         }
     }
 }
@@ -69,12 +142,28 @@ impl Beverley {
     const EPSILON: f32 = 1e-6;
 
     fn update_crushers(&mut self) {
-        let bit_depth = self.params.bit_depth.value();
-        let gamma = self.params.gamma.value();
+        let mut bit_depth = self.params.bit_depth.value();
+        let mut gamma = self.params.gamma.value();
         let smoothing = self.params.smoothing.value();
-        let gain_db: f32 = self.params.gain_db.value();
+        let mut gain_db: f32 = self.params.gain_db.value();
         let auto_gain = self.params.auto_gain.value();
         let crush_mode = self.params.crush_mode.value();
+        let oversampling = self.params.oversampling.value();
+        let sample_rate_reduction = self.params.sample_rate_reduction.value();
+        let filter_cutoff = self.params.filter_cutoff.value();
+        let filter_resonance = self.params.filter_resonance.value();
+
+        let lfo_rate = self.params.lfo_rate.value();
+        let lfo_depth = self.params.lfo_depth.value();
+        let lfo_target = self.params.lfo_target.value();
+        let lfo_waveform = self.lfo_waveform();
+        let lfo_value = self.lfo.advance(lfo_rate, self.sample_rate, lfo_waveform);
+
+        match lfo_target {
+            1 => gamma = (gamma + lfo_depth * lfo_value).clamp(-1.0, 1.0),
+            2 => gain_db = (gain_db + lfo_depth * lfo_value * 20.0).clamp(0.0, 20.0),
+            _ => bit_depth = (bit_depth + lfo_depth * lfo_value * 15.0).clamp(1.0, 16.0),
+        }
 
         self.update_crushers_if_changed(
             bit_depth,
@@ -82,7 +171,11 @@ impl Beverley {
             smoothing,
             gain_db,
             auto_gain,
-            crush_mode
+            crush_mode,
+            oversampling,
+            sample_rate_reduction,
+            filter_cutoff,
+            filter_resonance,
         );
     }
 
@@ -95,6 +188,10 @@ impl Beverley {
         gain_db: f32,
         auto_gain: bool,
         crush_mode: bool,
+        oversampling: i32,
+        sample_rate_reduction: f32,
+        filter_cutoff: f32,
+        filter_resonance: f32,
     ) {
         let bit_depth_changed = (bit_depth - self.cached_bit_depth).abs() > Self::EPSILON;
         let gamma_changed = (gamma - self.cached_gamma).abs() > Self::EPSILON;
@@ -102,6 +199,11 @@ impl Beverley {
         let gain_db_changed = (gain_db - self.cached_gain_db).abs() > Self::EPSILON;
         let auto_gain_changed = auto_gain != self.cached_auto_gain;
         let crush_mode_changed = crush_mode != self.cached_crush_mode;
+        let oversampling_changed = oversampling != self.cached_oversampling;
+        let sample_rate_reduction_changed =
+            (sample_rate_reduction - self.cached_sample_rate_reduction).abs() > Self::EPSILON;
+        let filter_changed = (filter_cutoff - self.cached_filter_cutoff).abs() > Self::EPSILON
+            || (filter_resonance - self.cached_filter_resonance).abs() > Self::EPSILON;
 
         if bit_depth_changed {
             self.crusher_left.set_depth(bit_depth);
@@ -144,6 +246,110 @@ impl Beverley {
             self.crusher_right.set_crush_mode(crush_mode);
             self.cached_crush_mode = crush_mode;
         }
+
+        if oversampling_changed {
+            let factor = (1 << oversampling) as f32;
+            self.oversampler_left.set_factor(factor);
+            self.oversampler_right.set_factor(factor);
+            self.cached_oversampling = oversampling;
+        }
+
+        if sample_rate_reduction_changed {
+            self.cached_sample_rate_reduction = sample_rate_reduction;
+        }
+
+        // The crusher runs at the oversampled rate, so the reduction
+        // ratio must be re-derived whenever either it or the oversampling
+        // factor changes.
+        if oversampling_changed || sample_rate_reduction_changed {
+            let oversampled_rate = self.sample_rate * self.oversampler_left.factor() as f32;
+            let ratio = self.cached_sample_rate_reduction / oversampled_rate;
+            self.crusher_left.set_sample_rate_reduction(ratio);
+            self.crusher_right.set_sample_rate_reduction(ratio);
+        }
+
+        if filter_changed {
+            self.filter_pre_left.set_coefficients(filter_cutoff, filter_resonance, self.sample_rate);
+            self.filter_pre_right.set_coefficients(filter_cutoff, filter_resonance, self.sample_rate);
+            self.filter_post_left.set_coefficients(filter_cutoff, filter_resonance, self.sample_rate);
+            self.filter_post_right.set_coefficients(filter_cutoff, filter_resonance, self.sample_rate);
+            self.cached_filter_cutoff = filter_cutoff;
+            self.cached_filter_resonance = filter_resonance;
+        }
+    }
+
+    /// Filter latency introduced by the active oversampling stages, in
+    /// samples at the host sample rate.
+    fn latency_samples(&self) -> u32 {
+        self.oversampler_left.latency_samples().round() as u32
+    }
+
+    fn filter_mode(&self) -> FilterMode {
+        match self.params.filter_mode.value() {
+            1 => FilterMode::BandPass,
+            2 => FilterMode::HighPass,
+            3 => FilterMode::Notch,
+            _ => FilterMode::LowPass,
+        }
+    }
+
+    fn lfo_waveform(&self) -> LfoWaveform {
+        match self.params.lfo_waveform.value() {
+            1 => LfoWaveform::Triangle,
+            2 => LfoWaveform::SampleAndHold,
+            _ => LfoWaveform::Sine,
+        }
+    }
+
+    /// Runs a single sample through the oversampled crusher for the given
+    /// channel, with the tone-shaping filter inserted pre- and/or
+    /// post-crush as selected by `filter_position`.
+    fn process_channel(&mut self, input: f32, is_left: bool) -> f32 {
+        let position = self.params.filter_position.value();
+        let mode = self.filter_mode();
+        let dc_block = self.params.dc_block.value();
+
+        let (oversampler, crusher, filter_pre, filter_post, dc_blocker) = if is_left {
+            (
+                &mut self.oversampler_left,
+                &mut self.crusher_left,
+                &mut self.filter_pre_left,
+                &mut self.filter_post_left,
+                &mut self.dc_blocker_left,
+            )
+        } else {
+            (
+                &mut self.oversampler_right,
+                &mut self.crusher_right,
+                &mut self.filter_pre_right,
+                &mut self.filter_post_right,
+                &mut self.dc_blocker_right,
+            )
+        };
+
+        let pre_filtered = if position == 1 || position == 3 {
+            filter_pre.process(input, mode)
+        } else {
+            input
+        };
+
+        let mut upsampled = oversampler.upsample(pre_filtered);
+        for sample in upsampled[..oversampler.factor()].iter_mut() {
+            *sample = crusher.apply(*sample);
+        }
+        let crushed = oversampler.downsample(&upsampled);
+
+        let post_filtered = if position == 2 || position == 3 {
+            filter_post.process(crushed, mode)
+        } else {
+            crushed
+        };
+
+        if dc_block {
+            dc_blocker.process(post_filtered)
+        } else {
+            post_filtered
+        }
     }
 }
 
@@ -186,6 +392,116 @@ impl Default for BeverleyParams {
             auto_gain: BoolParam::new("Auto Gain", false),
 
             crush_mode: BoolParam::new("Symmetric", false),
+
+            oversampling: IntParam::new(
+                "Oversampling",
+                0,
+                IntRange::Linear { min: 0, max: 3 },
+            )
+            .with_value_to_string(Arc::new(|value| format!("{}x", 1 << value))),
+
+            sample_rate_reduction: FloatParam::new(
+                "Sample Rate Reduction",
+                48000.0,
+                FloatRange::Linear { min: 200.0, max: 48000.0 },
+            )
+            .with_step_size(1.0)
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+
+            filter_cutoff: FloatParam::new(
+                "Filter Cutoff",
+                1000.0,
+                FloatRange::Linear { min: 20.0, max: 20000.0 },
+            )
+            .with_step_size(1.0)
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+
+            filter_resonance: FloatParam::new(
+                "Filter Resonance",
+                0.707,
+                FloatRange::Linear { min: 0.5, max: 10.0 },
+            )
+            .with_step_size(0.01)
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+
+            filter_mode: IntParam::new(
+                "Filter Mode",
+                0,
+                IntRange::Linear { min: 0, max: 3 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                match value {
+                    1 => "Bandpass",
+                    2 => "Highpass",
+                    3 => "Notch",
+                    _ => "Lowpass",
+                }
+                .to_string()
+            })),
+
+            filter_position: IntParam::new(
+                "Filter Position",
+                0,
+                IntRange::Linear { min: 0, max: 3 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                match value {
+                    1 => "Pre",
+                    2 => "Post",
+                    3 => "Both",
+                    _ => "Off",
+                }
+                .to_string()
+            })),
+
+            dc_block: BoolParam::new("DC Block", true),
+
+            lfo_rate: FloatParam::new(
+                "LFO Rate",
+                1.0,
+                FloatRange::Linear { min: 0.01, max: 20.0 },
+            )
+            .with_step_size(0.01)
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+
+            lfo_depth: FloatParam::new(
+                "LFO Depth",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_step_size(0.01)
+            .with_value_to_string(formatters::v2s_f32_percentage(0)),
+
+            lfo_target: IntParam::new(
+                "LFO Target",
+                0,
+                IntRange::Linear { min: 0, max: 2 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                match value {
+                    1 => "Gamma",
+                    2 => "Gain",
+                    _ => "Bit Depth",
+                }
+                .to_string()
+            })),
+
+            lfo_waveform: IntParam::new(
+                "LFO Waveform",
+                0,
+                IntRange::Linear { min: 0, max: 2 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                match value {
+                    1 => "Triangle",
+                    2 => "Sample & Hold",
+                    _ => "Sine",
+                }
+                .to_string()
+            })),
         }
     }
 }
@@ -223,15 +539,42 @@ impl Plugin for Beverley {
         &mut self,
         _audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
-        _context: &mut impl InitContext<Self>,
+        context: &mut impl InitContext<Self>,
     ) -> bool {
         // Reinitialize crushers with correct sample rate
         let sample_rate = buffer_config.sample_rate;
+        self.sample_rate = sample_rate;
         self.crusher_left = InterpolatedBitCrusher::new(sample_rate);
         self.crusher_right = InterpolatedBitCrusher::new(sample_rate);
+        self.oversampler_left.reset();
+        self.oversampler_right.reset();
+        self.dc_blocker_left.set_sample_rate(sample_rate);
+        self.dc_blocker_right.set_sample_rate(sample_rate);
+
+        // Recompute filter coefficients for the host's sample rate; update_crushers()
+        // below only recomputes these when the cutoff/resonance params have changed,
+        // which they haven't on the first call, so this must happen explicitly.
+        let filter_cutoff = self.params.filter_cutoff.value();
+        let filter_resonance = self.params.filter_resonance.value();
+        self.filter_pre_left.set_coefficients(filter_cutoff, filter_resonance, sample_rate);
+        self.filter_pre_right.set_coefficients(filter_cutoff, filter_resonance, sample_rate);
+        self.filter_post_left.set_coefficients(filter_cutoff, filter_resonance, sample_rate);
+        self.filter_post_right.set_coefficients(filter_cutoff, filter_resonance, sample_rate);
+        self.cached_filter_cutoff = filter_cutoff;
+        self.cached_filter_resonance = filter_resonance;
+
+        // The crushers were just recreated with no sample rate reduction, and
+        // update_crushers() only re-derives the ratio on a parameter change, so
+        // derive it here for the new sample rate as well.
+        let sample_rate_reduction = self.params.sample_rate_reduction.value();
+        let ratio = sample_rate_reduction / (sample_rate * self.oversampler_left.factor() as f32);
+        self.crusher_left.set_sample_rate_reduction(ratio);
+        self.crusher_right.set_sample_rate_reduction(ratio);
+        self.cached_sample_rate_reduction = sample_rate_reduction;
 
         // Set initial parameters
         self.update_crushers();
+        context.set_latency_samples(self.latency_samples());
 
         true
     }
@@ -239,13 +582,22 @@ impl Plugin for Beverley {
     fn reset(&mut self) {
         self.crusher_left.reset();
         self.crusher_right.reset();
+        self.oversampler_left.reset();
+        self.oversampler_right.reset();
+        self.filter_pre_left.reset();
+        self.filter_pre_right.reset();
+        self.filter_post_left.reset();
+        self.filter_post_right.reset();
+        self.dc_blocker_left.reset();
+        self.dc_blocker_right.reset();
+        self.lfo.reset();
     }
 
     fn process(
         &mut self,
         buffer: &mut Buffer,
         _aux: &mut AuxiliaryBuffers,
-        _context: &mut impl ProcessContext<Self>,
+        context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
         let num_samples = buffer.samples();
         let channels = buffer.as_slice();
@@ -256,7 +608,7 @@ impl Plugin for Beverley {
             for sample_idx in 0..num_samples {
                 self.update_crushers();
                 let sample = &mut left_channel[sample_idx];
-                *sample = self.crusher_left.apply(*sample);
+                *sample = self.process_channel(*sample, true);
             }
         } else {
             // Stereo - process both channels
@@ -267,13 +619,15 @@ impl Plugin for Beverley {
                 self.update_crushers();
 
                 let left_sample = &mut left_channel[sample_idx];
-                *left_sample = self.crusher_left.apply(*left_sample);
+                *left_sample = self.process_channel(*left_sample, true);
 
                 let right_sample = &mut right_channel[sample_idx];
-                *right_sample = self.crusher_right.apply(*right_sample);
+                *right_sample = self.process_channel(*right_sample, false);
             }
         }
 
+        context.set_latency_samples(self.latency_samples());
+
         ProcessStatus::Normal
     }
 }